@@ -0,0 +1,361 @@
+use spdx::expression::{ExprNode, Operator};
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    sync::LazyLock,
+};
+
+use crate::utils::ResultExt;
+
+/// SPDX license identifier → nixpkgs `lib.licenses` attribute name.
+static SPDX_TO_NIX: &[(&str, &str)] = &[
+    ("0BSD", "bsd0"),
+    ("AGPL-3.0-only", "agpl3Only"),
+    ("AGPL-3.0-or-later", "agpl3Plus"),
+    ("Apache-2.0", "asl20"),
+    ("BSD-2-Clause", "bsd2"),
+    ("BSD-3-Clause", "bsd3"),
+    ("CC0-1.0", "cc0"),
+    ("GPL-2.0-only", "gpl2Only"),
+    ("GPL-2.0-or-later", "gpl2Plus"),
+    ("GPL-3.0-only", "gpl3Only"),
+    ("GPL-3.0-or-later", "gpl3Plus"),
+    ("ISC", "isc"),
+    ("LGPL-2.1-only", "lgpl21Only"),
+    ("LGPL-2.1-or-later", "lgpl21Plus"),
+    ("LGPL-3.0-only", "lgpl3Only"),
+    ("LGPL-3.0-or-later", "lgpl3Plus"),
+    ("MIT", "mit"),
+    ("MPL-2.0", "mpl20"),
+    ("Unlicense", "unlicense"),
+    ("Zlib", "zlib"),
+];
+
+/// `SPDX-id WITH exception-id` pairs that nixpkgs exposes as a dedicated
+/// `lib.licenses` attribute. Anything not listed falls back to the base
+/// license with the exception dropped.
+static WITH_EXCEPTION_TO_NIX: &[(&str, &str, &str)] = &[
+    ("GPL-2.0-or-later", "Classpath-exception-2.0", "gpl2ClasspathPlus"),
+    ("GPL-2.0-only", "Classpath-exception-2.0", "gpl2Classpath"),
+];
+
+/// Map an SPDX identifier to its nixpkgs `lib.licenses` attribute, ignoring
+/// case and a trailing `+`.
+pub fn get_nix_license(spdx: &str) -> Option<&'static str> {
+    let id = spdx.trim().trim_end_matches('+');
+    SPDX_TO_NIX
+        .iter()
+        .find(|(spdx, _)| spdx.eq_ignore_ascii_case(id))
+        .map(|(_, nix)| *nix)
+}
+
+/// Resolve a single SPDX requirement — an identifier, its `-or-later` flag, and
+/// an optional `WITH` exception — to a nixpkgs license attribute.
+fn resolve_requirement(id: &str, or_later: bool, exception: Option<&str>) -> Option<&'static str> {
+    // A `+`/`-or-later` requirement is the deprecated `GPL-2.0+` form. `spdx`
+    // hands back the base id (`GPL-2.0`, or sometimes the `-only` spelling) with
+    // `or_later` set, so normalize to the `-or-later` id the table keys on before
+    // looking it up.
+    let id = if or_later {
+        let base = id.trim_end_matches("-or-later").trim_end_matches("-only");
+        format!("{base}-or-later")
+    } else {
+        id.to_owned()
+    };
+
+    if let Some(exception) = exception {
+        if let Some((.., nix)) = WITH_EXCEPTION_TO_NIX
+            .iter()
+            .find(|(base, exc, _)| base.eq_ignore_ascii_case(&id) && exc.eq_ignore_ascii_case(exception))
+        {
+            return Some(*nix);
+        }
+    }
+
+    get_nix_license(&id)
+}
+
+/// Parse a full SPDX expression and emit the set of nixpkgs license attributes
+/// it selects, each paired with a weight. A single license carries full weight
+/// and `AND` keeps each operand's weight unchanged; `OR` operands are
+/// down-weighted so the selector treats them as alternatives the user may pick
+/// between rather than a hard requirement.
+pub fn parse_spdx_expression(expr: &str, _source: &str) -> Vec<(&'static str, f32)> {
+    // Parse in lax mode so deprecated forms like `GPL-2.0+` and imprecise casing
+    // are accepted rather than rejected outright.
+    let Some(expr) = spdx::Expression::parse_mode(expr, spdx::ParseMode::LAX).ok_warn() else {
+        return Vec::new();
+    };
+
+    // Evaluate the postfix node stream into a single weighted set.
+    let mut stack: Vec<BTreeMap<&'static str, f32>> = Vec::new();
+    for node in expr.iter() {
+        match node {
+            ExprNode::Req(req) => {
+                let mut set = BTreeMap::new();
+                if let spdx::LicenseItem::Spdx { id, or_later } = req.req.license {
+                    let exception = req.req.exception.map(|e| e.id.name);
+                    if let Some(nix) = resolve_requirement(id.name, *or_later, exception) {
+                        set.insert(nix, 1.0);
+                    }
+                }
+                stack.push(set);
+            }
+            ExprNode::Op(op) => {
+                let (Some(rhs), Some(lhs)) = (stack.pop(), stack.pop()) else {
+                    continue;
+                };
+                stack.push(merge(lhs, rhs, *op));
+            }
+        }
+    }
+
+    stack
+        .pop()
+        .map(|set| set.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Combine two operand sets. `AND` keeps every license at its current weight;
+/// `OR` caps each alternative at `0.5` so it ranks below an unconditional
+/// license. Capping (rather than repeatedly halving) keeps every alternative of
+/// a 3+-way `OR` at the same weight regardless of parse associativity.
+fn merge(
+    lhs: BTreeMap<&'static str, f32>,
+    rhs: BTreeMap<&'static str, f32>,
+    op: Operator,
+) -> BTreeMap<&'static str, f32> {
+    let mut out = BTreeMap::new();
+    for (id, weight) in lhs.into_iter().chain(rhs) {
+        let weight = if matches!(op, Operator::Or) {
+            weight.min(0.5)
+        } else {
+            weight
+        };
+        let entry = out.entry(id).or_insert(weight);
+        if weight > *entry {
+            *entry = weight;
+        }
+    }
+    out
+}
+
+/// Canonical SPDX license templates used to identify the contents of a
+/// `LICENSE`/`COPYING` file when the package metadata carries no declared
+/// license. Kept small and embedded; the text is normalized once on first use.
+static TEMPLATES: &[(&str, &str)] = &[
+    ("MIT", include_str!("licenses/MIT.txt")),
+    ("BSD-3-Clause", include_str!("licenses/BSD-3-Clause.txt")),
+    ("BSD-2-Clause", include_str!("licenses/BSD-2-Clause.txt")),
+    ("ISC", include_str!("licenses/ISC.txt")),
+];
+
+/// Normalized bigram sets for each bundled template, built once.
+static NORMALIZED_TEMPLATES: LazyLock<Vec<(&'static str, Vec<[u8; 2]>)>> = LazyLock::new(|| {
+    TEMPLATES
+        .iter()
+        .map(|(id, text)| (*id, bigrams(&normalize(text))))
+        .collect()
+});
+
+/// Lowercase, collapse whitespace runs to a single space, and drop a leading
+/// copyright/notice block (per-project lines that would otherwise skew the
+/// comparison).
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_preamble = true;
+    for line in text.lines() {
+        let line = line.trim();
+        // Drop the leading preamble — the license title, the per-project
+        // copyright/notice lines, and blank lines between them — wherever it
+        // appears before the first body sentence, not only at byte 0.
+        if in_preamble {
+            if is_notice_line(line) {
+                continue;
+            }
+            in_preamble = false;
+        }
+        if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+        let mut prev_space = false;
+        for c in line.chars() {
+            if c.is_whitespace() {
+                if !prev_space && !out.ends_with(' ') {
+                    out.push(' ');
+                }
+                prev_space = true;
+            } else {
+                out.extend(c.to_lowercase());
+                prev_space = false;
+            }
+        }
+    }
+    out.trim().to_owned()
+}
+
+/// Whether a line belongs to the per-project copyright/notice preamble — a
+/// blank line, a `Copyright`/`(c)`/`All rights reserved` notice, or a bare
+/// license title such as `MIT License` that precedes the body.
+fn is_notice_line(line: &str) -> bool {
+    let lower = line.to_ascii_lowercase();
+    line.is_empty()
+        || lower.starts_with("copyright")
+        || lower.starts_with("(c)")
+        || lower.starts_with("all rights reserved")
+        || lower.ends_with("license")
+}
+
+/// Byte bigrams of `s`, used for the Sørensen–Dice similarity below.
+fn bigrams(s: &str) -> Vec<[u8; 2]> {
+    let bytes = s.as_bytes();
+    bytes.windows(2).map(|w| [w[0], w[1]]).collect()
+}
+
+/// Sørensen–Dice coefficient over bigram multisets, in `0.0..=1.0`.
+fn dice(a: &[[u8; 2]], b: &[[u8; 2]]) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut counts: BTreeMap<[u8; 2], i32> = BTreeMap::new();
+    for g in a {
+        *counts.entry(*g).or_default() += 1;
+    }
+    let mut overlap = 0usize;
+    for g in b {
+        if let Some(n) = counts.get_mut(g) {
+            if *n > 0 {
+                *n -= 1;
+                overlap += 1;
+            }
+        }
+    }
+    2.0 * overlap as f32 / (a.len() + b.len()) as f32
+}
+
+/// Walk `dir` for `LICENSE*`/`COPYING*`/`LICENCE*`/`NOTICE*` files, match their
+/// contents against the bundled SPDX templates, and insert any high-confidence
+/// match into `licenses` weighted by its similarity score. This lets the
+/// downstream selector rank a detected license against a declared one.
+pub fn scan_license_files(dir: &Path, licenses: &mut BTreeMap<&'static str, f32>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !is_license_file(name) {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+
+        match_license_text(&text, licenses);
+    }
+}
+
+/// Match a single license file's `text` against the bundled SPDX templates and,
+/// when the best match clears the confidence threshold, fuse it into `licenses`
+/// weighted by its similarity score.
+pub fn match_license_text(text: &str, licenses: &mut BTreeMap<&'static str, f32>) {
+    let candidate = bigrams(&normalize(text));
+    let best = NORMALIZED_TEMPLATES
+        .iter()
+        .map(|(id, template)| (*id, dice(&candidate, template)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((id, score)) = best {
+        if score >= 0.9 {
+            if let Some(nix) = get_nix_license(id) {
+                let weight = licenses.entry(nix).or_insert(score);
+                if score > *weight {
+                    *weight = score;
+                }
+            }
+        }
+    }
+}
+
+/// Resolve each `pattern` (a filename or a shell-style glob such as
+/// `LICEN[CS]E*`) against `dir` and feed the contents of every matching file
+/// through [`match_license_text`].
+pub fn scan_license_globs(dir: &Path, patterns: &[String], licenses: &mut BTreeMap<&'static str, f32>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let names: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| Some((entry.path(), entry.file_name().into_string().ok()?)))
+        .collect();
+
+    for pattern in patterns {
+        for (path, name) in &names {
+            if glob_match(pattern, name) {
+                if let Ok(text) = fs::read_to_string(path) {
+                    match_license_text(&text, licenses);
+                }
+            }
+        }
+    }
+}
+
+/// Minimal shell-style glob matcher supporting `*`, `?`, and `[...]` character
+/// classes — enough for the license-file patterns packaging metadata uses.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pat: &[u8], name: &[u8]) -> bool {
+        match pat.first() {
+            None => name.is_empty(),
+            Some(b'*') => matches(&pat[1..], name) || (!name.is_empty() && matches(pat, &name[1..])),
+            Some(b'?') => !name.is_empty() && matches(&pat[1..], &name[1..]),
+            Some(b'[') => {
+                let Some(end) = pat.iter().position(|&c| c == b']') else {
+                    return false;
+                };
+                let Some(&c) = name.first() else {
+                    return false;
+                };
+                pat[1..end].contains(&c) && matches(&pat[end + 1..], &name[1..])
+            }
+            Some(&p) => name.first() == Some(&p) && matches(&pat[1..], &name[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), name.as_bytes())
+}
+
+/// Whether `name` looks like a license/notice file (case-insensitive).
+fn is_license_file(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["LICENSE", "LICENCE", "COPYING", "NOTICE"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_spdx_expression;
+
+    #[test]
+    fn or_later() {
+        // The deprecated `+` form must normalize to the `-or-later` attribute.
+        assert_eq!(parse_spdx_expression("GPL-2.0+", "test"), vec![("gpl2Plus", 1.0)]);
+        assert_eq!(
+            parse_spdx_expression("GPL-3.0+", "test"),
+            vec![("gpl3Plus", 1.0)],
+        );
+    }
+
+    #[test]
+    fn or_alternatives_share_weight() {
+        // Every alternative of a 3-way `OR` ranks equally regardless of nesting.
+        let mut got = parse_spdx_expression("MIT OR Apache-2.0 OR BSD-3-Clause", "test");
+        got.sort_by(|a, b| a.0.cmp(b.0));
+        assert_eq!(got, vec![("asl20", 0.5), ("bsd3", 0.5), ("mit", 0.5)]);
+    }
+}
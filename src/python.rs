@@ -8,13 +8,32 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{license::parse_spdx_expression, utils::ResultExt};
+use crate::{
+    license::{parse_spdx_expression, scan_license_files, scan_license_globs},
+    utils::ResultExt,
+};
 
+#[serde_as]
 #[derive(Default, Deserialize)]
 #[serde(default)]
 pub struct Pyproject {
     project: Project,
     tool: Tool,
+    #[serde_as(as = "DefaultOnError")]
+    #[serde(rename = "dependency-groups")]
+    dependency_groups: BTreeMap<String, Vec<DependencyGroupEntry>>,
+}
+
+/// An entry in a PEP 735 `[dependency-groups]` list: a requirement string or an
+/// `{ include-group = "..." }` reference to another group.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DependencyGroupEntry {
+    Requirement(String),
+    Include {
+        #[serde(rename = "include-group")]
+        include_group: String,
+    },
 }
 
 #[serde_as]
@@ -22,8 +41,24 @@ pub struct Pyproject {
 struct Project {
     name: Option<String>,
     #[serde_as(as = "DefaultOnError")]
-    license: Option<String>,
+    license: Option<License>,
+    #[serde_as(as = "DefaultOnError")]
+    #[serde(rename = "license-files")]
+    license_files: Option<Vec<String>>,
     dependencies: Option<Vec<String>>,
+    #[serde_as(as = "DefaultOnError")]
+    #[serde(default, rename = "optional-dependencies")]
+    optional_dependencies: BTreeMap<String, Vec<String>>,
+}
+
+/// The `project.license` field as it may appear under PEP 621/639: a bare SPDX
+/// string, a `{ text = "..." }` table, or a `{ file = "LICENSE" }` table.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum License {
+    Spdx(String),
+    Text { text: String },
+    File { file: String },
 }
 
 #[derive(Default, Deserialize)]
@@ -40,6 +75,19 @@ struct Poetry {
     license: Option<String>,
     #[serde_as(as = "Option<Map<_, DefaultOnError>>")]
     dependencies: Option<BTreeSet<(String, ())>>,
+    #[serde(default)]
+    group: BTreeMap<String, PoetryGroup>,
+}
+
+/// A `[tool.poetry.group.<name>]` table. Its dependencies may be bare version
+/// strings or inline `{ version = "..." }` tables; either way we only need the
+/// distribution names, so values deserialize through `DefaultOnError` to unit.
+#[serde_as]
+#[derive(Default, Deserialize)]
+struct PoetryGroup {
+    #[serde_as(as = "Map<_, DefaultOnError>")]
+    #[serde(default)]
+    dependencies: BTreeSet<(String, ())>,
 }
 
 impl Pyproject {
@@ -54,31 +102,175 @@ impl Pyproject {
             .or_else(|| self.tool.poetry.name.take())
     }
 
-    pub fn load_license(&self, licenses: &mut BTreeMap<&'static str, f32>) {
-        if let Some(license) = self
-            .project
-            .license
-            .as_ref()
-            .or(self.tool.poetry.license.as_ref())
-        {
-            for license in parse_spdx_expression(license, "pyproject.toml") {
-                licenses.insert(license, 1.0);
+    pub fn load_license(&self, dir: &Path, licenses: &mut BTreeMap<&'static str, f32>) {
+        match &self.project.license {
+            Some(License::Spdx(expr)) => load_spdx_expression(expr, licenses),
+            Some(License::Text { text }) => load_spdx_expression(text, licenses),
+            Some(License::File { file }) => {
+                scan_license_globs(dir, std::slice::from_ref(file), licenses);
             }
+            None => {
+                if let Some(license) = self.tool.poetry.license.as_ref() {
+                    load_spdx_expression(license, licenses);
+                }
+            }
+        }
+
+        if let Some(files) = &self.project.license_files {
+            scan_license_globs(dir, files, licenses);
+        }
+
+        // When the declared metadata yields nothing — the common case of a
+        // project that ships a `LICENSE`/`COPYING` file but leaves the metadata
+        // empty — fall back to scanning the source directory for one.
+        if licenses.is_empty() {
+            scan_license_files(dir, licenses);
         }
     }
 
     pub fn get_dependencies(&mut self) -> Option<BTreeSet<String>> {
-        if let Some(deps) = self.project.dependencies.take() {
-            Some(deps.into_iter().filter_map(get_python_dependency).collect())
+        // Preserve the historical contract: `Some` only when a required-set
+        // source (`[project].dependencies` or `[tool.poetry].dependencies`) was
+        // actually present, so callers keep their `None` fallback when a manifest
+        // declares only optional groups.
+        let had_required =
+            self.project.dependencies.is_some() || self.tool.poetry.dependencies.is_some();
+        let deps = self.get_dependencies_grouped();
+        had_required.then_some(deps.required)
+    }
+
+    /// Collect every dependency bucket the manifest declares: the always-required
+    /// set (`[project].dependencies` or `[tool.poetry].dependencies`) plus each
+    /// optional group keyed by its extra/group name — PEP 621
+    /// `[project.optional-dependencies]`, Poetry `[tool.poetry.group.*]`, and
+    /// PEP 735 `[dependency-groups]`. The caller maps the required set to
+    /// `propagatedBuildInputs` and routes test/dev groups to `nativeCheckInputs`.
+    pub fn get_dependencies_grouped(&mut self) -> Dependencies {
+        let env = MarkerEnv::default();
+        let mut optional = BTreeMap::new();
+
+        let required = if let Some(deps) = self.project.dependencies.take() {
+            resolve_requirements(&deps, &env)
         } else if let Some(mut deps) = self.tool.poetry.dependencies.take() {
             deps.remove(&("python".into(), ()));
-            Some(
-                deps.into_iter()
-                    .map(|(dep, _)| dep.to_lowercase().replace(['_', '.'], "-"))
-                    .collect(),
-            )
+            deps.into_iter().map(|(dep, _)| normalize_name(&dep)).collect()
         } else {
-            None
+            BTreeSet::new()
+        };
+
+        // PEP 621 extras: resolve each extra's requirements with that extra
+        // selected so `extra`-gated markers inside it evaluate to true.
+        for (extra, deps) in std::mem::take(&mut self.project.optional_dependencies) {
+            let env = MarkerEnv {
+                extras: vec![extra.clone()],
+                ..MarkerEnv::default()
+            };
+            optional
+                .entry(extra)
+                .or_default()
+                .extend(resolve_requirements(&deps, &env));
+        }
+
+        // Poetry groups; the implicit `python` entry is stripped as above.
+        for (group, spec) in std::mem::take(&mut self.tool.poetry.group) {
+            let mut deps = spec.dependencies;
+            deps.remove(&("python".into(), ()));
+            optional
+                .entry(group)
+                .or_default()
+                .extend(deps.into_iter().map(|(dep, _)| normalize_name(&dep)));
+        }
+
+        // PEP 735 dependency-groups, resolving `include-group` references.
+        let groups = std::mem::take(&mut self.dependency_groups);
+        for name in groups.keys() {
+            let mut seen = BTreeSet::new();
+            optional
+                .entry(name.clone())
+                .or_default()
+                .extend(resolve_group(name, &groups, &env, &mut seen));
+        }
+
+        Dependencies { required, optional }
+    }
+}
+
+/// The dependency buckets declared by a `pyproject.toml`: the always-installed
+/// set and the optional groups (extras, Poetry groups, PEP 735 groups) keyed by
+/// name.
+#[derive(Default, Debug, PartialEq)]
+pub struct Dependencies {
+    pub required: BTreeSet<String>,
+    pub optional: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Parse a list of PEP 508 requirement strings into normalized names, dropping
+/// those whose environment marker excludes them under `env`.
+fn resolve_requirements(deps: &[String], env: &MarkerEnv) -> BTreeSet<String> {
+    deps.iter()
+        .filter_map(|dep| parse_pep508(dep))
+        .filter(|spec| marker_selected(spec, env))
+        .map(|spec| spec.name)
+        .collect()
+}
+
+/// Whether `spec`'s environment marker admits it under `env`. A requirement that
+/// itself requests extras (`pkg[test]`) has those extras treated as active while
+/// its marker is evaluated, so an `extra`-gated requirement is pulled in exactly
+/// when the extra it asks for is selected.
+fn marker_selected(spec: &Specifier, env: &MarkerEnv) -> bool {
+    match &spec.marker {
+        None => true,
+        Some(marker) if spec.extras.is_empty() => marker.eval(env),
+        Some(marker) => {
+            let mut env = env.clone();
+            env.extras.extend(spec.extras.iter().cloned());
+            marker.eval(&env)
+        }
+    }
+}
+
+/// Resolve a PEP 735 dependency group, expanding `include-group` references.
+/// `seen` guards against cyclic includes.
+fn resolve_group<'a>(
+    name: &'a str,
+    groups: &'a BTreeMap<String, Vec<DependencyGroupEntry>>,
+    env: &MarkerEnv,
+    seen: &mut BTreeSet<&'a str>,
+) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    if !seen.insert(name) {
+        return out;
+    }
+    let Some(entries) = groups.get_key_value(name) else {
+        return out;
+    };
+    for entry in entries.1 {
+        match entry {
+            DependencyGroupEntry::Requirement(dep) => {
+                if let Some(spec) = parse_pep508(dep) {
+                    if marker_selected(&spec, env) {
+                        out.insert(spec.name);
+                    }
+                }
+            }
+            DependencyGroupEntry::Include { include_group } => {
+                if let Some((key, _)) = groups.get_key_value(include_group.as_str()) {
+                    out.extend(resolve_group(key, groups, env, seen));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Parse an SPDX expression and fuse its weighted attributes into `licenses`,
+/// keeping the highest weight seen for any given attribute.
+fn load_spdx_expression(expr: &str, licenses: &mut BTreeMap<&'static str, f32>) {
+    for (license, weight) in parse_spdx_expression(expr, "pyproject.toml") {
+        let entry = licenses.entry(license).or_insert(weight);
+        if weight > *entry {
+            *entry = weight;
         }
     }
 }
@@ -92,37 +284,389 @@ pub fn parse_requirements_txt(src: &Path) -> Option<BTreeSet<String>> {
     })
 }
 
+/// Extract the normalized distribution name from a dependency line, keeping the
+/// historical contract used by `requirements.txt` parsing and the tests: a line
+/// that does not start with a distribution name yields `None`, everything from
+/// the first specifier character onward is ignored.
 pub fn get_python_dependency(dep: String) -> Option<String> {
-    let mut chars = dep.chars().skip_while(|c| c.is_whitespace());
+    parse_pep508(&dep).map(|spec| spec.name)
+}
 
-    let x = chars.next()?;
-    if !x.is_alphabetic() {
-        return None;
+/// A parsed [PEP 508] dependency specifier: the normalized distribution name,
+/// its requested extras, the raw version constraints, and the environment
+/// marker gating the requirement (if any).
+///
+/// [PEP 508]: https://peps.python.org/pep-0508/
+#[derive(Debug, PartialEq)]
+pub struct Specifier {
+    pub name: String,
+    pub extras: Vec<String>,
+    pub version: Option<String>,
+    pub marker: Option<Marker>,
+}
+
+/// An environment-marker expression, as a small AST over the variables and
+/// operators PEP 508 permits. Unrecognized variables are preserved verbatim and
+/// treated conservatively (see [`Marker::eval`]).
+#[derive(Debug, PartialEq)]
+pub enum Marker {
+    And(Box<Marker>, Box<Marker>),
+    Or(Box<Marker>, Box<Marker>),
+    Compare {
+        var: String,
+        op: MarkerOp,
+        value: String,
+        /// Whether the variable was the left operand (`var op "lit"`). Preserved
+        /// so `in`/`not in` and ordering comparisons evaluate in source order.
+        var_left: bool,
+    },
+}
+
+/// A marker comparison operator.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MarkerOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    NotIn,
+}
+
+/// The target environment a marker is evaluated against: a Python version, a
+/// platform, and a chosen set of active extras. The default is a fixed Python
+/// 3.12 on Linux (`linux`/`posix`) with no extras; it is not derived from the
+/// interpreter being packaged.
+#[derive(Clone)]
+pub struct MarkerEnv {
+    pub python_version: String,
+    pub sys_platform: String,
+    pub os_name: String,
+    pub extras: Vec<String>,
+}
+
+impl Default for MarkerEnv {
+    fn default() -> Self {
+        MarkerEnv {
+            python_version: "3.12".into(),
+            sys_platform: "linux".into(),
+            os_name: "posix".into(),
+            extras: Vec::new(),
+        }
     }
-    let mut name = String::from(x.to_ascii_lowercase());
+}
 
-    while let Some(c) = chars.next() {
-        if c.is_alphabetic() {
-            name.push(c.to_ascii_lowercase());
-        } else if matches!(c, '-' | '.' | '_') {
-            match chars.next() {
-                Some(c) if c.is_alphabetic() => {
-                    name.push('-');
-                    name.push(c.to_ascii_lowercase());
+impl Marker {
+    /// Evaluate the marker against `env`. A comparison over a variable we do not
+    /// model resolves to `true` so that requirements we cannot reason about are
+    /// kept rather than silently dropped.
+    pub fn eval(&self, env: &MarkerEnv) -> bool {
+        match self {
+            Marker::And(lhs, rhs) => lhs.eval(env) && rhs.eval(env),
+            Marker::Or(lhs, rhs) => lhs.eval(env) || rhs.eval(env),
+            Marker::Compare {
+                var,
+                op,
+                value,
+                var_left,
+            } => {
+                // Restore source order so `"lit" op var` comparisons (and the
+                // direction of `in`/`<`) evaluate correctly.
+                let order = |env_val: &str| {
+                    if *var_left {
+                        (env_val.to_owned(), value.clone())
+                    } else {
+                        (value.clone(), env_val.to_owned())
+                    }
+                };
+                match var.as_str() {
+                    "python_version" | "python_full_version" => {
+                        let (l, r) = order(&env.python_version);
+                        compare_versions(&l, *op, &r)
+                    }
+                    "sys_platform" => {
+                        let (l, r) = order(&env.sys_platform);
+                        compare_strings(&l, *op, &r)
+                    }
+                    "os_name" => {
+                        let (l, r) = order(&env.os_name);
+                        compare_strings(&l, *op, &r)
+                    }
+                    "extra" => {
+                        let present = env.extras.iter().any(|e| e == value);
+                        match op {
+                            MarkerOp::Eq | MarkerOp::In => present,
+                            MarkerOp::Ne | MarkerOp::NotIn => !present,
+                            _ => true,
+                        }
+                    }
+                    _ => true,
                 }
-                _ => break,
             }
+        }
+    }
+}
+
+/// Compare two dotted numeric versions under a marker operator.
+fn compare_versions(lhs: &str, op: MarkerOp, rhs: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split('.')
+            .map(|p| p.trim().parse().unwrap_or(0))
+            .collect()
+    };
+    let (mut a, mut b) = (parse(lhs), parse(rhs));
+    // Zero-pad the shorter release so `3.12` and `3.12.0` compare equal rather
+    // than the prefix ranking below the longer form.
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    match op {
+        MarkerOp::Eq => a == b,
+        MarkerOp::Ne => a != b,
+        MarkerOp::Lt => a < b,
+        MarkerOp::Le => a <= b,
+        MarkerOp::Gt => a > b,
+        MarkerOp::Ge => a >= b,
+        // `a in b` holds when `a` is a substring of `b`.
+        MarkerOp::In => rhs.contains(lhs),
+        MarkerOp::NotIn => !rhs.contains(lhs),
+    }
+}
+
+/// Compare two string-valued marker operands.
+fn compare_strings(lhs: &str, op: MarkerOp, rhs: &str) -> bool {
+    match op {
+        MarkerOp::Eq => lhs == rhs,
+        MarkerOp::Ne => lhs != rhs,
+        MarkerOp::Lt => lhs < rhs,
+        MarkerOp::Le => lhs <= rhs,
+        MarkerOp::Gt => lhs > rhs,
+        MarkerOp::Ge => lhs >= rhs,
+        // `a in b` holds when `a` is a substring of `b`.
+        MarkerOp::In => rhs.contains(lhs),
+        MarkerOp::NotIn => !rhs.contains(lhs),
+    }
+}
+
+/// Parse a PEP 508 dependency specifier. Returns `None` when the line does not
+/// begin with a distribution name (blank lines, comments, option lines). The
+/// name is normalized like the rest of the pipeline: lowercased with runs of
+/// `_`/`.`/`-` folded to a single `-`.
+pub fn parse_pep508(dep: &str) -> Option<Specifier> {
+    let dep = dep.trim();
+
+    // Split the requirement from its marker at the first top-level `;`; the
+    // marker grammar never produces a bare `;`, so the first one always starts
+    // the marker section.
+    let (req, marker) = match dep.split_once(';') {
+        Some((req, marker)) => (req.trim(), Some(marker.trim())),
+        None => (dep, None),
+    };
+
+    let bytes = req.as_bytes();
+    let mut i = 0;
+    if bytes.first().is_none_or(|c| !c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    while i < bytes.len() && matches!(bytes[i], b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_') {
+        i += 1;
+    }
+    let name = normalize_name(&req[..i]);
+    let rest = req[i..].trim_start();
+
+    // Optional `[extra1, extra2]`.
+    let (extras, rest) = if let Some(rest) = rest.strip_prefix('[') {
+        let (inner, rest) = rest.split_once(']')?;
+        let extras = inner
+            .split(',')
+            .map(|e| normalize_name(e.trim()))
+            .filter(|e| !e.is_empty())
+            .collect();
+        (extras, rest.trim_start())
+    } else {
+        (Vec::new(), rest)
+    };
+
+    let version = (!rest.is_empty()).then(|| rest.trim().to_owned());
+    let marker = marker.and_then(parse_marker);
+
+    Some(Specifier {
+        name,
+        extras,
+        version,
+        marker,
+    })
+}
+
+/// Normalize a distribution/extra name: lowercase with `_`/`.`/`-` runs folded
+/// to a single `-`.
+fn normalize_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut prev_sep = false;
+    for c in name.chars() {
+        if matches!(c, '-' | '.' | '_') {
+            if !prev_sep && !out.is_empty() {
+                out.push('-');
+            }
+            prev_sep = true;
         } else {
-            break;
+            out.extend(c.to_lowercase());
+            prev_sep = false;
         }
     }
+    out.trim_end_matches('-').to_owned()
+}
+
+/// Parse a marker expression into a [`Marker`] AST. Returns `None` on malformed
+/// input so the caller keeps the requirement unconditionally.
+fn parse_marker(src: &str) -> Option<Marker> {
+    let tokens = tokenize_marker(src)?;
+    let mut pos = 0;
+    let marker = parse_or(&tokens, &mut pos)?;
+    (pos == tokens.len()).then_some(marker)
+}
+
+/// A marker token: structural punctuation, boolean keywords, comparison
+/// operators, variable identifiers, and quoted string literals.
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Op(MarkerOp),
+    Ident(String),
+    Str(String),
+}
+
+fn tokenize_marker(src: &str) -> Option<Vec<Token>> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b' ' | b'\t' => i += 1,
+            b'(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            b')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            b'\'' | b'"' => {
+                let quote = bytes[i];
+                let start = i + 1;
+                let end = start + bytes[start..].iter().position(|&c| c == quote)?;
+                tokens.push(Token::Str(src[start..end].to_owned()));
+                i = end + 1;
+            }
+            b'=' | b'!' | b'<' | b'>' | b'~' => {
+                let rest = &src[i..];
+                // Match the longest operator spelling first; `~=` and `===` both
+                // collapse to equality for our purposes.
+                let (op, len) = if rest.starts_with("===") {
+                    (MarkerOp::Eq, 3)
+                } else if rest.starts_with("==") {
+                    (MarkerOp::Eq, 2)
+                } else if rest.starts_with("!=") {
+                    (MarkerOp::Ne, 2)
+                } else if rest.starts_with("<=") {
+                    (MarkerOp::Le, 2)
+                } else if rest.starts_with(">=") {
+                    (MarkerOp::Ge, 2)
+                } else if rest.starts_with("~=") {
+                    (MarkerOp::Eq, 2)
+                } else {
+                    match bytes[i] {
+                        b'<' => (MarkerOp::Lt, 1),
+                        b'>' => (MarkerOp::Gt, 1),
+                        _ => return None,
+                    }
+                };
+                i += len;
+                tokens.push(Token::Op(op));
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                let start = i;
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_')
+                {
+                    i += 1;
+                }
+                match &src[start..i] {
+                    "and" => tokens.push(Token::And),
+                    "or" => tokens.push(Token::Or),
+                    "in" => tokens.push(Token::Op(MarkerOp::In)),
+                    "not" => {
+                        // `not in`: skip whitespace and the following `in`.
+                        let tail = src[i..].trim_start();
+                        let rest = tail.strip_prefix("in")?;
+                        i = src.len() - rest.len();
+                        tokens.push(Token::Op(MarkerOp::NotIn));
+                    }
+                    ident => tokens.push(Token::Ident(ident.to_owned())),
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+fn parse_or(tokens: &[Token], pos: &mut usize) -> Option<Marker> {
+    let mut lhs = parse_and(tokens, pos)?;
+    while matches!(tokens.get(*pos), Some(Token::Or)) {
+        *pos += 1;
+        let rhs = parse_and(tokens, pos)?;
+        lhs = Marker::Or(Box::new(lhs), Box::new(rhs));
+    }
+    Some(lhs)
+}
+
+fn parse_and(tokens: &[Token], pos: &mut usize) -> Option<Marker> {
+    let mut lhs = parse_primary(tokens, pos)?;
+    while matches!(tokens.get(*pos), Some(Token::And)) {
+        *pos += 1;
+        let rhs = parse_primary(tokens, pos)?;
+        lhs = Marker::And(Box::new(lhs), Box::new(rhs));
+    }
+    Some(lhs)
+}
+
+fn parse_primary(tokens: &[Token], pos: &mut usize) -> Option<Marker> {
+    if matches!(tokens.get(*pos), Some(Token::Open)) {
+        *pos += 1;
+        let marker = parse_or(tokens, pos)?;
+        matches!(tokens.get(*pos), Some(Token::Close)).then(|| *pos += 1)?;
+        return Some(marker);
+    }
 
-    Some(name)
+    // A comparison: variable and string literal in either order.
+    let (var, value, op, var_left) =
+        match (tokens.get(*pos), tokens.get(*pos + 1), tokens.get(*pos + 2)) {
+            (Some(Token::Ident(var)), Some(Token::Op(op)), Some(Token::Str(value))) => {
+                (var.clone(), value.clone(), *op, true)
+            }
+            (Some(Token::Str(value)), Some(Token::Op(op)), Some(Token::Ident(var))) => {
+                (var.clone(), value.clone(), *op, false)
+            }
+            _ => return None,
+        };
+    *pos += 3;
+    Some(Marker::Compare {
+        var,
+        op,
+        value,
+        var_left,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::get_python_dependency;
+    use super::{get_python_dependency, parse_pep508, MarkerEnv, MarkerOp, Specifier};
 
     #[test]
     fn basic() {
@@ -142,4 +686,101 @@ mod tests {
         assert_eq!(get_python_dependency("".into()), None);
         assert_eq!(get_python_dependency("# comment".into()), None);
     }
+
+    #[test]
+    fn extras_and_version() {
+        let spec = parse_pep508("requests[security, socks] >= 2.0").unwrap();
+        assert_eq!(spec.name, "requests");
+        assert_eq!(spec.extras, ["security", "socks"]);
+        assert_eq!(spec.version.as_deref(), Some(">= 2.0"));
+        assert!(spec.marker.is_none());
+    }
+
+    #[test]
+    fn marker_compare() {
+        let spec = parse_pep508("tomli ; python_version < \"3.11\"").unwrap();
+        let marker = spec.marker.unwrap();
+        assert_eq!(
+            marker,
+            super::Marker::Compare {
+                var: "python_version".into(),
+                op: MarkerOp::Lt,
+                value: "3.11".into(),
+                var_left: true,
+            },
+        );
+        // Default environment packages a newer interpreter, so the marker is
+        // false and the requirement is gated out.
+        assert!(!marker.eval(&MarkerEnv::default()));
+    }
+
+    #[test]
+    fn marker_boolean_and_extra() {
+        let spec =
+            parse_pep508("pytest ; sys_platform == 'win32' or extra == 'test'").unwrap();
+        let marker = spec.marker.unwrap();
+        assert!(!marker.eval(&MarkerEnv::default()));
+        assert!(marker.eval(&MarkerEnv {
+            extras: vec!["test".into()],
+            ..MarkerEnv::default()
+        }));
+    }
+
+    #[test]
+    fn grouped_dependencies() {
+        let src = r#"
+            [project]
+            dependencies = ["requests", "tomli ; python_version < '3.11'"]
+
+            [project.optional-dependencies]
+            docs = ["sphinx"]
+
+            [dependency-groups]
+            test = ["pytest", "pytest-cov"]
+            dev = ["ruff", {include-group = "test"}]
+        "#;
+        let mut pyproject: super::Pyproject = toml::from_str(src).unwrap();
+        let deps = pyproject.get_dependencies_grouped();
+
+        // `tomli` is gated to an older interpreter and drops out.
+        assert_eq!(deps.required, set(["requests"]));
+        assert_eq!(deps.optional["docs"], set(["sphinx"]));
+        assert_eq!(deps.optional["test"], set(["pytest", "pytest-cov"]));
+        // `dev` pulls in the `test` group via `include-group`.
+        assert_eq!(deps.optional["dev"], set(["pytest", "pytest-cov", "ruff"]));
+    }
+
+    #[test]
+    fn requested_extras_activate_marker() {
+        let env = MarkerEnv::default();
+        // A requirement that asks for its own extra has that extra treated as
+        // selected when its `extra`-gated marker is evaluated.
+        assert!(super::marker_selected(
+            &parse_pep508("pkg[foo] ; extra == 'foo'").unwrap(),
+            &env,
+        ));
+        assert!(!super::marker_selected(
+            &parse_pep508("pkg ; extra == 'foo'").unwrap(),
+            &env,
+        ));
+    }
+
+    fn set<const N: usize>(names: [&str; N]) -> std::collections::BTreeSet<String> {
+        names.into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn no_name() {
+        assert_eq!(parse_pep508(""), None);
+        assert_eq!(parse_pep508("# comment"), None);
+        assert_eq!(
+            parse_pep508("name.with_dots"),
+            Some(Specifier {
+                name: "name-with-dots".into(),
+                extras: vec![],
+                version: None,
+                marker: None,
+            }),
+        );
+    }
 }